@@ -1,4 +1,4 @@
-use hft_rust::{OrderBook, Side};
+use hft_rust::{Event, OrderBook, Side};
 use std::time::Instant;
 
 const WARMUP_OPS: usize = 1_000;
@@ -12,6 +12,8 @@ fn main() {
     bench_execute_order();
     bench_best_bid_ask();
     bench_throughput();
+    bench_deep_book();
+    bench_recorded_session_replay();
 
     println!("=== Benchmark Complete ===");
 }
@@ -23,7 +25,7 @@ fn bench_add_order() {
     for i in 0..WARMUP_OPS {
         let price = 90000 + (i as u32 % 20000);
         let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
-        book.add_order(i as u64, side, price, 100);
+        book.add_order(i as u64, side, price, 100, i as u64).unwrap();
     }
 
     // Reset
@@ -33,7 +35,7 @@ fn bench_add_order() {
     for i in 0..BENCH_OPS {
         let price = 90000 + (i as u32 % 20000);
         let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
-        book.add_order(i as u64, side, price, 100);
+        book.add_order(i as u64, side, price, 100, i as u64).unwrap();
     }
     let elapsed = start.elapsed();
 
@@ -50,12 +52,12 @@ fn bench_cancel_order() {
     for i in 0..BENCH_OPS {
         let price = 100000 + (i as u32 % 1000);
         let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
-        book.add_order(i as u64, side, price, 100);
+        book.add_order(i as u64, side, price, 100, i as u64).unwrap();
     }
 
     let start = Instant::now();
     for i in 0..BENCH_OPS {
-        book.cancel_order(i as u64);
+        book.cancel_order(i as u64, i as u64);
     }
     let elapsed = start.elapsed();
 
@@ -72,12 +74,12 @@ fn bench_execute_order() {
     for i in 0..BENCH_OPS {
         let price = 100000 + (i as u32 % 1000);
         let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
-        book.add_order(i as u64, side, price, 1000);
+        book.add_order(i as u64, side, price, 1000, i as u64).unwrap();
     }
 
     let start = Instant::now();
     for i in 0..BENCH_OPS {
-        book.execute_order(i as u64, 10);
+        book.execute_order(i as u64, 10, i as u64);
     }
     let elapsed = start.elapsed();
 
@@ -94,7 +96,7 @@ fn bench_best_bid_ask() {
     for i in 0..10_000 {
         let price = 100000 + (i as u32 % 100);
         let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
-        book.add_order(i as u64, side, price, 100);
+        book.add_order(i as u64, side, price, 100, i as u64).unwrap();
     }
 
     let start = Instant::now();
@@ -123,10 +125,10 @@ fn bench_throughput() {
     for i in 0..OPS {
         let price = 100000 + (i as u32 % 1000);
         let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
-        book.add_order((i % 100_000) as u64, side, price, 100);
+        book.add_order((i % 100_000) as u64, side, price, 100, i as u64).unwrap();
 
         if i % 3 == 0 {
-            book.cancel_order((i % 100_000) as u64);
+            book.cancel_order((i % 100_000) as u64, i as u64);
         }
     }
     let elapsed = start.elapsed();
@@ -138,3 +140,85 @@ fn bench_throughput() {
     println!("  {:.2} million ops/sec", ops_per_sec / 1_000_000.0);
     println!("  {:.2} ns/op average\n", ns_per_op);
 }
+
+/// Pre-fills tens of thousands of distinct price levels before timing
+/// add/cancel, so level lookup cost (not order-within-level cost) dominates
+/// the measurement. This is the workload that a linear level scan degrades
+/// on as the book gets deep.
+fn bench_deep_book() {
+    const DEPTH: usize = 50_000;
+    let mut book = OrderBook::new();
+
+    // Pre-fill with one order per distinct price level on each side
+    for i in 0..DEPTH {
+        book.add_order(i as u64, Side::Buy, 50_000 - (i as u32 % 50_000), 100, i as u64).unwrap();
+        book.add_order((DEPTH + i) as u64, Side::Sell, 50_000 + (i as u32 % 50_000), 100, i as u64).unwrap();
+    }
+
+    let start = Instant::now();
+    for i in 0..BENCH_OPS {
+        let price = 50_000 + (i as u32 % DEPTH as u32);
+        book.add_order((2 * DEPTH + i) as u64, Side::Sell, price, 100, i as u64).unwrap();
+    }
+    let elapsed = start.elapsed();
+    let ns_per_op = elapsed.as_nanos() as f64 / BENCH_OPS as f64;
+    println!("Add Order (deep book, {} distinct levels/side):", DEPTH);
+    println!("  Count: {} ops", BENCH_OPS);
+    println!("  Mean:  {:.1} ns/op\n", ns_per_op);
+
+    let start = Instant::now();
+    for i in 0..DEPTH {
+        book.cancel_order(i as u64, i as u64);
+    }
+    let elapsed = start.elapsed();
+    let ns_per_op = elapsed.as_nanos() as f64 / DEPTH as f64;
+    println!("Cancel Order (deep book, {} distinct levels/side):", DEPTH);
+    println!("  Count: {} ops", DEPTH);
+    println!("  Mean:  {:.1} ns/op\n", ns_per_op);
+}
+
+/// Builds a synthetic session as an `Event` log (adds alternating buy/sell
+/// over 40 adjacent price levels, a partial fill every 5th order and a
+/// cancel every 7th, each aimed at an earlier order) and times replaying it
+/// end to end with `OrderBook::replay`.
+fn bench_recorded_session_replay() {
+    const SESSION_OPS: usize = 50_000;
+
+    let mut log: Vec<Event> = Vec::with_capacity(SESSION_OPS);
+    for i in 0..SESSION_OPS {
+        let id = i as u64;
+        let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+        let price = 100000 + (i as u32 % 40);
+        log.push(Event::Add { timestamp: id, order_id: id, side, price, quantity: 100 });
+
+        if i % 5 == 0 && i >= 10 {
+            let order_id = id - 10;
+            log.push(Event::ExecutionVisible {
+                timestamp: id,
+                order_id,
+                side: if order_id % 2 == 0 { Side::Buy } else { Side::Sell },
+                price: 100000 + (order_id as u32 % 40),
+                quantity: 20,
+            });
+        }
+        if i % 7 == 0 && i >= 14 {
+            let order_id = id - 14;
+            log.push(Event::Cancel {
+                timestamp: id,
+                order_id,
+                side: if order_id % 2 == 0 { Side::Buy } else { Side::Sell },
+                price: 100000 + (order_id as u32 % 40),
+                quantity: 100,
+            });
+        }
+    }
+
+    let start = Instant::now();
+    let replayed = OrderBook::replay(&log);
+    let elapsed = start.elapsed();
+
+    std::hint::black_box(replayed.best_bid());
+    let ns_per_event = elapsed.as_nanos() as f64 / log.len() as f64;
+    println!("Recorded Session Replay ({} events):", log.len());
+    println!("  Mean:  {:.1} ns/event\n", ns_per_event);
+}
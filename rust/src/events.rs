@@ -0,0 +1,154 @@
+//! Append-only event log mirroring the LOBSTER market-message schema, so a
+//! book's state transitions can be recorded and replayed offline.
+
+use crate::types::*;
+use std::fmt;
+
+/// A single book state transition, carrying everything needed to replay it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A new order started resting on the book
+    Add { timestamp: u64, order_id: OrderId, side: Side, price: Price, quantity: Quantity },
+    /// A resting order was canceled by its owner; `quantity` is how much was resting
+    Cancel { timestamp: u64, order_id: OrderId, side: Side, price: Price, quantity: Quantity },
+    /// A resting order was filled, partially or fully; `quantity` is the amount filled
+    ExecutionVisible { timestamp: u64, order_id: OrderId, side: Side, price: Price, quantity: Quantity },
+    /// A resting order was fully removed from the book because a fill
+    /// exhausted it; `quantity` is the amount filled by that final execution
+    Delete { timestamp: u64, order_id: OrderId, side: Side, price: Price, quantity: Quantity },
+}
+
+impl Event {
+    pub fn timestamp(&self) -> u64 {
+        match *self {
+            Event::Add { timestamp, .. }
+            | Event::Cancel { timestamp, .. }
+            | Event::ExecutionVisible { timestamp, .. }
+            | Event::Delete { timestamp, .. } => timestamp,
+        }
+    }
+
+    pub fn order_id(&self) -> OrderId {
+        match *self {
+            Event::Add { order_id, .. }
+            | Event::Cancel { order_id, .. }
+            | Event::ExecutionVisible { order_id, .. }
+            | Event::Delete { order_id, .. } => order_id,
+        }
+    }
+
+    /// Serialize as `timestamp,type,order_id,side,price,quantity`
+    pub fn to_csv(&self) -> String {
+        let (ty, timestamp, order_id, side, price, quantity) = match *self {
+            Event::Add { timestamp, order_id, side, price, quantity } => {
+                ("ADD", timestamp, order_id, side, price, quantity)
+            }
+            Event::Cancel { timestamp, order_id, side, price, quantity } => {
+                ("CANCEL", timestamp, order_id, side, price, quantity)
+            }
+            Event::ExecutionVisible { timestamp, order_id, side, price, quantity } => {
+                ("EXECUTION", timestamp, order_id, side, price, quantity)
+            }
+            Event::Delete { timestamp, order_id, side, price, quantity } => {
+                ("DELETE", timestamp, order_id, side, price, quantity)
+            }
+        };
+        let side = match side {
+            Side::Buy => "B",
+            Side::Sell => "S",
+        };
+        format!("{timestamp},{ty},{order_id},{side},{price},{quantity}")
+    }
+
+    /// Parse a line produced by [`Event::to_csv`]
+    pub fn from_csv(line: &str) -> Result<Event, ParseEventError> {
+        let mut fields = line.trim().split(',');
+        let mut next = |name: &'static str| fields.next().ok_or(ParseEventError::MissingField(name));
+
+        let timestamp: u64 =
+            next("timestamp")?.parse().map_err(|_| ParseEventError::InvalidField("timestamp"))?;
+        let ty = next("type")?;
+        let order_id: OrderId =
+            next("order_id")?.parse().map_err(|_| ParseEventError::InvalidField("order_id"))?;
+        let side = match next("side")? {
+            "B" => Side::Buy,
+            "S" => Side::Sell,
+            _ => return Err(ParseEventError::InvalidField("side")),
+        };
+        let price: Price = next("price")?.parse().map_err(|_| ParseEventError::InvalidField("price"))?;
+        let quantity: Quantity =
+            next("quantity")?.parse().map_err(|_| ParseEventError::InvalidField("quantity"))?;
+
+        match ty {
+            "ADD" => Ok(Event::Add { timestamp, order_id, side, price, quantity }),
+            "CANCEL" => Ok(Event::Cancel { timestamp, order_id, side, price, quantity }),
+            "EXECUTION" => Ok(Event::ExecutionVisible { timestamp, order_id, side, price, quantity }),
+            "DELETE" => Ok(Event::Delete { timestamp, order_id, side, price, quantity }),
+            _ => Err(ParseEventError::InvalidField("type")),
+        }
+    }
+}
+
+/// Why a CSV line couldn't be parsed into an [`Event`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseEventError {
+    MissingField(&'static str),
+    InvalidField(&'static str),
+}
+
+impl fmt::Display for ParseEventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseEventError::MissingField(name) => write!(f, "missing field: {name}"),
+            ParseEventError::InvalidField(name) => write!(f, "invalid field: {name}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseEventError {}
+
+/// Receives book state transitions as they happen. Implement this to
+/// stream a session to disk/network, or use the `Vec<Event>` impl below to
+/// just buffer it in memory.
+pub trait EventSink {
+    fn on_event(&mut self, event: Event);
+}
+
+impl EventSink for Vec<Event> {
+    fn on_event(&mut self, event: Event) {
+        self.push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_roundtrip_for_every_variant() {
+        let events = [
+            Event::Add { timestamp: 1, order_id: 10, side: Side::Buy, price: 10000, quantity: 50 },
+            Event::Cancel { timestamp: 2, order_id: 10, side: Side::Buy, price: 10000, quantity: 50 },
+            Event::ExecutionVisible { timestamp: 3, order_id: 11, side: Side::Sell, price: 10100, quantity: 25 },
+            Event::Delete { timestamp: 4, order_id: 11, side: Side::Sell, price: 10100, quantity: 25 },
+        ];
+
+        for event in events {
+            let line = event.to_csv();
+            assert_eq!(Event::from_csv(&line), Ok(event));
+        }
+    }
+
+    #[test]
+    fn test_from_csv_rejects_malformed_line() {
+        assert!(Event::from_csv("not,enough,fields").is_err());
+        assert!(Event::from_csv("1,UNKNOWN,10,B,10000,50").is_err());
+    }
+
+    #[test]
+    fn test_vec_event_sink_buffers_events() {
+        let mut sink: Vec<Event> = Vec::new();
+        sink.on_event(Event::Add { timestamp: 1, order_id: 1, side: Side::Buy, price: 100, quantity: 1 });
+        assert_eq!(sink.len(), 1);
+    }
+}
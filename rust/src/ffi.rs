@@ -3,7 +3,8 @@
 //! This module provides C-compatible functions for interoperability
 //! with C/C++ code.
 
-use crate::orderbook::OrderBook;
+use crate::matching_engine::{MatchingEngine, OrderType, Trade};
+use crate::orderbook::{LevelView, OrderBook, OrderBookError};
 use crate::types::*;
 use std::os::raw::c_char;
 
@@ -38,6 +39,27 @@ impl From<Side> for HftSide {
     }
 }
 
+/// Status code returned by FFI functions that can fail
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HftStatus {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidTickSize = 2,
+    InvalidLotSize = 3,
+    BelowMinimumSize = 4,
+}
+
+impl From<OrderBookError> for HftStatus {
+    fn from(e: OrderBookError) -> Self {
+        match e {
+            OrderBookError::InvalidTickSize => HftStatus::InvalidTickSize,
+            OrderBookError::InvalidLotSize => HftStatus::InvalidLotSize,
+            OrderBookError::BelowMinimumSize => HftStatus::BelowMinimumSize,
+        }
+    }
+}
+
 /// Quote structure for C FFI
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -48,6 +70,15 @@ pub struct HftQuote {
     pub ask_size: Quantity,
 }
 
+/// Aggregated price level for C FFI, as returned by the depth functions
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct HftPriceLevel {
+    pub price: Price,
+    pub quantity: Quantity,
+    pub order_count: u32,
+}
+
 /// Trade structure for C FFI
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -60,6 +91,47 @@ pub struct HftTrade {
     pub timestamp: u64,
 }
 
+impl From<Trade> for HftTrade {
+    fn from(t: Trade) -> Self {
+        HftTrade {
+            aggressive_order_id: t.aggressive_order_id,
+            passive_order_id: t.passive_order_id,
+            price: t.price,
+            quantity: t.quantity,
+            aggressor_side: t.aggressor_side.into(),
+            timestamp: t.timestamp,
+        }
+    }
+}
+
+/// Order type for C FFI (matches C API)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HftOrderType {
+    Limit = 0,
+    Market = 1,
+    ImmediateOrCancel = 2,
+    FillOrKill = 3,
+    PostOnly = 4,
+}
+
+impl From<HftOrderType> for OrderType {
+    fn from(t: HftOrderType) -> Self {
+        match t {
+            HftOrderType::Limit => OrderType::Limit,
+            HftOrderType::Market => OrderType::Market,
+            HftOrderType::ImmediateOrCancel => OrderType::ImmediateOrCancel,
+            HftOrderType::FillOrKill => OrderType::FillOrKill,
+            HftOrderType::PostOnly => OrderType::PostOnly,
+        }
+    }
+}
+
+/// Opaque MatchingEngine handle for C FFI
+pub struct HftMatchingEngine {
+    inner: MatchingEngine,
+}
+
 // ============================================
 // OrderBook FFI Functions
 // ============================================
@@ -89,6 +161,9 @@ pub unsafe extern "C" fn hft_rust_orderbook_destroy(book: *mut HftOrderBook) {
 
 /// Add an order to the book
 ///
+/// Returns `HftStatus::Ok` on success, or a status describing which
+/// `MarketConfig` constraint the order violated.
+///
 /// # Safety
 /// The book pointer must be valid
 #[no_mangle]
@@ -98,14 +173,17 @@ pub unsafe extern "C" fn hft_rust_orderbook_add_order(
     side: HftSide,
     price: Price,
     quantity: Quantity,
-) -> bool {
+    timestamp: u64,
+) -> HftStatus {
     if book.is_null() {
-        return false;
+        return HftStatus::NullPointer;
     }
 
     let book = &mut *book;
-    book.inner.add_order(order_id, side.into(), price, quantity);
-    true
+    match book.inner.add_order(order_id, side.into(), price, quantity, timestamp) {
+        Ok(()) => HftStatus::Ok,
+        Err(e) => e.into(),
+    }
 }
 
 /// Cancel an order
@@ -116,13 +194,14 @@ pub unsafe extern "C" fn hft_rust_orderbook_add_order(
 pub unsafe extern "C" fn hft_rust_orderbook_cancel_order(
     book: *mut HftOrderBook,
     order_id: OrderId,
+    timestamp: u64,
 ) -> bool {
     if book.is_null() {
         return false;
     }
 
     let book = &mut *book;
-    book.inner.cancel_order(order_id)
+    book.inner.cancel_order(order_id, timestamp)
 }
 
 /// Execute (partial fill) an order
@@ -134,13 +213,14 @@ pub unsafe extern "C" fn hft_rust_orderbook_execute_order(
     book: *mut HftOrderBook,
     order_id: OrderId,
     quantity: Quantity,
+    timestamp: u64,
 ) -> bool {
     if book.is_null() {
         return false;
     }
 
     let book = &mut *book;
-    book.inner.execute_order(order_id, quantity)
+    book.inner.execute_order(order_id, quantity, timestamp)
 }
 
 /// Get best bid price
@@ -205,6 +285,160 @@ pub unsafe extern "C" fn hft_rust_orderbook_ask_quantity_at(
     book.inner.ask_quantity_at(price)
 }
 
+/// Fill `out` with up to `max_levels` bid levels, best-to-worst
+///
+/// Returns the number of levels written.
+///
+/// # Safety
+/// The book pointer must be valid, and `out` must point to an array of at
+/// least `max_levels` `HftPriceLevel` elements.
+#[no_mangle]
+pub unsafe extern "C" fn hft_rust_orderbook_bid_depth(
+    book: *const HftOrderBook,
+    out: *mut HftPriceLevel,
+    max_levels: usize,
+) -> usize {
+    if book.is_null() || out.is_null() {
+        return 0;
+    }
+
+    let book = &*book;
+    fill_depth(book.inner.iter_side(Side::Buy), out, max_levels)
+}
+
+/// Fill `out` with up to `max_levels` ask levels, best-to-worst
+///
+/// Returns the number of levels written.
+///
+/// # Safety
+/// The book pointer must be valid, and `out` must point to an array of at
+/// least `max_levels` `HftPriceLevel` elements.
+#[no_mangle]
+pub unsafe extern "C" fn hft_rust_orderbook_ask_depth(
+    book: *const HftOrderBook,
+    out: *mut HftPriceLevel,
+    max_levels: usize,
+) -> usize {
+    if book.is_null() || out.is_null() {
+        return 0;
+    }
+
+    let book = &*book;
+    fill_depth(book.inner.iter_side(Side::Sell), out, max_levels)
+}
+
+unsafe fn fill_depth(
+    levels: impl Iterator<Item = LevelView>,
+    out: *mut HftPriceLevel,
+    max_levels: usize,
+) -> usize {
+    let mut written = 0;
+    for level in levels.take(max_levels) {
+        *out.add(written) = HftPriceLevel {
+            price: level.price,
+            quantity: level.quantity,
+            order_count: level.order_count,
+        };
+        written += 1;
+    }
+    written
+}
+
+// ============================================
+// MatchingEngine FFI Functions
+// ============================================
+
+/// Create a new matching engine
+///
+/// # Safety
+/// Returns a raw pointer that must be freed with `hft_rust_matching_engine_destroy`
+#[no_mangle]
+pub extern "C" fn hft_rust_matching_engine_create() -> *mut HftMatchingEngine {
+    Box::into_raw(Box::new(HftMatchingEngine { inner: MatchingEngine::new() }))
+}
+
+/// Destroy a matching engine
+///
+/// # Safety
+/// The pointer must be valid and must have been created by `hft_rust_matching_engine_create`
+#[no_mangle]
+pub unsafe extern "C" fn hft_rust_matching_engine_destroy(engine: *mut HftMatchingEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+/// Submit an order for matching.
+///
+/// Writes up to `max_trades` resulting trades into `out_trades` and the
+/// number actually written into `out_count`. Returns `HftStatus::Ok` on
+/// success (even if it produced zero trades), or a status describing which
+/// `MarketConfig` constraint the order violated.
+///
+/// # Safety
+/// The engine pointer must be valid, `out_trades` must point to an array of
+/// at least `max_trades` `HftTrade` elements, and `out_count` must be valid
+/// to write to.
+#[no_mangle]
+pub unsafe extern "C" fn hft_rust_matching_engine_submit_order(
+    engine: *mut HftMatchingEngine,
+    order_id: OrderId,
+    side: HftSide,
+    order_type: HftOrderType,
+    price: Price,
+    quantity: Quantity,
+    timestamp: u64,
+    out_trades: *mut HftTrade,
+    max_trades: usize,
+    out_count: *mut usize,
+) -> HftStatus {
+    if engine.is_null() || out_trades.is_null() || out_count.is_null() {
+        return HftStatus::NullPointer;
+    }
+
+    let engine = &mut *engine;
+    match engine.inner.submit_order(order_id, side.into(), order_type.into(), price, quantity, timestamp) {
+        Ok(trades) => {
+            let written = trades.len().min(max_trades);
+            for (i, trade) in trades.into_iter().take(written).enumerate() {
+                *out_trades.add(i) = trade.into();
+            }
+            *out_count = written;
+            HftStatus::Ok
+        }
+        Err(e) => {
+            *out_count = 0;
+            e.into()
+        }
+    }
+}
+
+/// Get best bid price from the engine's book
+///
+/// # Safety
+/// The engine pointer must be valid
+#[no_mangle]
+pub unsafe extern "C" fn hft_rust_matching_engine_best_bid(engine: *const HftMatchingEngine) -> Price {
+    if engine.is_null() {
+        return INVALID_PRICE;
+    }
+
+    (&*engine).inner.book().best_bid()
+}
+
+/// Get best ask price from the engine's book
+///
+/// # Safety
+/// The engine pointer must be valid
+#[no_mangle]
+pub unsafe extern "C" fn hft_rust_matching_engine_best_ask(engine: *const HftMatchingEngine) -> Price {
+    if engine.is_null() {
+        return INVALID_PRICE;
+    }
+
+    (&*engine).inner.book().best_ask()
+}
+
 // ============================================
 // Utility Functions
 // ============================================
@@ -238,15 +472,15 @@ mod tests {
             assert!(!book.is_null());
 
             // Add order
-            let result = hft_rust_orderbook_add_order(book, 1, HftSide::Buy, 10000, 100);
-            assert!(result);
+            let result = hft_rust_orderbook_add_order(book, 1, HftSide::Buy, 10000, 100, 1);
+            assert_eq!(result, HftStatus::Ok);
 
             // Check best bid
             let bid = hft_rust_orderbook_best_bid(book);
             assert_eq!(bid, 10000);
 
             // Cancel order
-            let cancelled = hft_rust_orderbook_cancel_order(book, 1);
+            let cancelled = hft_rust_orderbook_cancel_order(book, 1, 2);
             assert!(cancelled);
 
             // Destroy
@@ -262,4 +496,79 @@ mod tests {
         let back = hft_rust_price_to_double(1502500);
         assert!((back - 150.25).abs() < 0.0001);
     }
+
+    #[test]
+    fn test_ffi_bid_depth_fills_buffer_best_to_worst() {
+        unsafe {
+            let book = hft_rust_orderbook_create();
+            hft_rust_orderbook_add_order(book, 1, HftSide::Buy, 10000, 100, 1);
+            hft_rust_orderbook_add_order(book, 2, HftSide::Buy, 10100, 50, 2);
+            hft_rust_orderbook_add_order(book, 3, HftSide::Buy, 9900, 25, 3);
+
+            let mut out = [HftPriceLevel { price: 0, quantity: 0, order_count: 0 }; 2];
+            let written = hft_rust_orderbook_bid_depth(book, out.as_mut_ptr(), out.len());
+
+            assert_eq!(written, 2);
+            assert_eq!(out[0].price, 10100);
+            assert_eq!(out[0].quantity, 50);
+            assert_eq!(out[1].price, 10000);
+            assert_eq!(out[1].quantity, 100);
+
+            hft_rust_orderbook_destroy(book);
+        }
+    }
+
+    #[test]
+    fn test_ffi_matching_engine_lifecycle() {
+        unsafe {
+            let engine = hft_rust_matching_engine_create();
+            assert!(!engine.is_null());
+
+            let mut out_trades = [HftTrade {
+                aggressive_order_id: 0,
+                passive_order_id: 0,
+                price: 0,
+                quantity: 0,
+                aggressor_side: HftSide::Buy,
+                timestamp: 0,
+            }; 4];
+            let mut out_count = 0usize;
+
+            let status = hft_rust_matching_engine_submit_order(
+                engine,
+                1,
+                HftSide::Sell,
+                HftOrderType::Limit,
+                10000,
+                100,
+                1,
+                out_trades.as_mut_ptr(),
+                out_trades.len(),
+                &mut out_count,
+            );
+            assert_eq!(status, HftStatus::Ok);
+            assert_eq!(out_count, 0);
+            assert_eq!(hft_rust_matching_engine_best_ask(engine), 10000);
+
+            let status = hft_rust_matching_engine_submit_order(
+                engine,
+                2,
+                HftSide::Buy,
+                HftOrderType::Limit,
+                10000,
+                150,
+                2,
+                out_trades.as_mut_ptr(),
+                out_trades.len(),
+                &mut out_count,
+            );
+            assert_eq!(status, HftStatus::Ok);
+            assert_eq!(out_count, 1);
+            assert_eq!(out_trades[0].passive_order_id, 1);
+            assert_eq!(out_trades[0].quantity, 100);
+            assert_eq!(hft_rust_matching_engine_best_bid(engine), 10000);
+
+            hft_rust_matching_engine_destroy(engine);
+        }
+    }
 }
@@ -1,8 +1,10 @@
 pub mod types;
 pub mod orderbook;
 pub mod matching_engine;
+pub mod events;
 pub mod ffi;
 
 pub use types::*;
-pub use orderbook::OrderBook;
-pub use matching_engine::{MatchingEngine, Trade};
+pub use orderbook::{DepthSnapshot, LevelView, MarketConfig, OrderBook, OrderBookError};
+pub use matching_engine::{MatchingEngine, OrderType, Trade};
+pub use events::{Event, EventSink, ParseEventError};
@@ -0,0 +1,335 @@
+//! Crossing logic for aggressive order submission.
+//!
+//! `OrderBook` only ever rests orders passively; `MatchingEngine` wraps a
+//! book and adds the logic that matches an incoming order against the
+//! resting opposite side, producing `Trade`s.
+
+use crate::orderbook::{MarketConfig, OrderBook, OrderBookError};
+use crate::types::*;
+
+/// How an incoming order should behave when it crosses the book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    /// Cross what it can, rest the remainder at its limit price
+    Limit,
+    /// Cross at any price, cancel any unfilled remainder
+    Market,
+    /// Cross up to its limit, cancel the remainder, never rests
+    ImmediateOrCancel,
+    /// Only execute if the full quantity can fill at or better than the
+    /// limit; otherwise the order has no effect on the book
+    FillOrKill,
+    /// Never crosses the book: if it would, it rests one tick behind the
+    /// best opposing order instead, or is rejected if no such price exists
+    PostOnly,
+}
+
+/// A single match between an aggressive and a passive order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trade {
+    pub aggressive_order_id: OrderId,
+    pub passive_order_id: OrderId,
+    pub price: Price,
+    pub quantity: Quantity,
+    pub aggressor_side: Side,
+    pub timestamp: u64,
+}
+
+/// Exchange core: an `OrderBook` plus the crossing logic that turns it
+/// into a working matching engine
+pub struct MatchingEngine {
+    book: OrderBook,
+}
+
+impl MatchingEngine {
+    pub fn new() -> Self {
+        Self { book: OrderBook::new() }
+    }
+
+    /// Create an engine whose book validates incoming orders against `config`
+    pub fn with_config(config: MarketConfig) -> Self {
+        Self { book: OrderBook::with_config(config) }
+    }
+
+    /// The underlying passive order book
+    pub fn book(&self) -> &OrderBook {
+        &self.book
+    }
+
+    /// Mutable access to the underlying book, e.g. to attach an
+    /// [`crate::events::EventSink`] or manage pegged orders directly
+    pub fn book_mut(&mut self) -> &mut OrderBook {
+        &mut self.book
+    }
+
+    /// Submit an order for matching. Returns the trades produced by
+    /// crossing against resting liquidity; any remainder is handled
+    /// according to `order_type`.
+    ///
+    /// Rejects the order against the book's [`MarketConfig`] before it
+    /// touches resting liquidity, the same granularity check a resting
+    /// order goes through in [`OrderBook::add_order`]. `Market` orders carry
+    /// no price of their own and so are only checked on quantity.
+    pub fn submit_order(
+        &mut self,
+        id: OrderId,
+        side: Side,
+        order_type: OrderType,
+        price: Price,
+        quantity: Quantity,
+        timestamp: u64,
+    ) -> Result<Vec<Trade>, OrderBookError> {
+        if order_type == OrderType::Market {
+            self.book.validate_quantity_order(quantity)?;
+        } else {
+            self.book.validate_order(price, quantity)?;
+        }
+
+        if order_type == OrderType::PostOnly {
+            return self.submit_post_only(id, side, price, quantity, timestamp);
+        }
+
+        let limit = match order_type {
+            OrderType::Market => None,
+            _ => Some(price),
+        };
+
+        if order_type == OrderType::FillOrKill
+            && self.book.quantity_within(side.opposite(), limit) < quantity as u64
+        {
+            return Ok(Vec::new());
+        }
+
+        let mut trades = Vec::new();
+        let mut remaining = quantity;
+        let opposing_side = side.opposite();
+
+        while remaining > 0 {
+            let Some(best_opp) = self.book.side_best(opposing_side) else { break };
+            if let Some(lim) = limit {
+                let crosses = match side {
+                    Side::Buy => best_opp <= lim,
+                    Side::Sell => best_opp >= lim,
+                };
+                if !crosses {
+                    break;
+                }
+            }
+
+            let Some(passive_id) = self.book.front_order_at(opposing_side, best_opp) else { break };
+            let passive_qty = self.book.order_quantity(passive_id).unwrap_or(0);
+            let fill_qty = remaining.min(passive_qty);
+
+            self.book.execute_order(passive_id, fill_qty, timestamp);
+            remaining -= fill_qty;
+
+            trades.push(Trade {
+                aggressive_order_id: id,
+                passive_order_id: passive_id,
+                price: best_opp,
+                quantity: fill_qty,
+                aggressor_side: side,
+                timestamp,
+            });
+        }
+
+        if order_type == OrderType::Limit && remaining > 0 {
+            // Already validated against the book's MarketConfig above, so
+            // the remainder is guaranteed to rest.
+            self.book.add_order(id, side, price, remaining, timestamp)?;
+        }
+
+        Ok(trades)
+    }
+
+    /// Rest a `PostOnly` order, sliding it one tick behind the best
+    /// opposing order if it would otherwise cross; rejected (no-op) if it
+    /// can't slide without crossing
+    fn submit_post_only(
+        &mut self,
+        id: OrderId,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        timestamp: u64,
+    ) -> Result<Vec<Trade>, OrderBookError> {
+        let mut rest_price = price;
+
+        if let Some(best_opp) = self.book.side_best(side.opposite()) {
+            let crosses = match side {
+                Side::Buy => price >= best_opp,
+                Side::Sell => price <= best_opp,
+            };
+
+            if crosses {
+                let tick = self.book.tick_size();
+                let slid = match side {
+                    Side::Buy => best_opp.checked_sub(tick),
+                    Side::Sell => best_opp.checked_add(tick),
+                };
+                let still_crosses = |p: Price| match side {
+                    Side::Buy => p >= best_opp,
+                    Side::Sell => p <= best_opp,
+                };
+                match slid {
+                    Some(p) if !still_crosses(p) => rest_price = p,
+                    _ => return Ok(Vec::new()),
+                }
+            }
+        }
+
+        self.book.add_order(id, side, rest_price, quantity, timestamp)?;
+        Ok(Vec::new())
+    }
+}
+
+impl Default for MatchingEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_order_rests_when_no_cross() {
+        let mut engine = MatchingEngine::new();
+        let trades = engine.submit_order(1, Side::Buy, OrderType::Limit, 10000, 100, 1).unwrap();
+
+        assert!(trades.is_empty());
+        assert_eq!(engine.book().best_bid(), 10000);
+    }
+
+    #[test]
+    fn test_limit_order_crosses_and_rests_remainder() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, 10000, 100, 1).unwrap();
+
+        let trades = engine.submit_order(2, Side::Buy, OrderType::Limit, 10000, 150, 2).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].passive_order_id, 1);
+        assert_eq!(trades[0].aggressive_order_id, 2);
+        assert_eq!(trades[0].quantity, 100);
+        assert_eq!(trades[0].price, 10000);
+        assert_eq!(engine.book().best_ask(), INVALID_PRICE);
+        assert_eq!(engine.book().bid_quantity_at(10000), 50);
+    }
+
+    #[test]
+    fn test_market_order_consumes_at_any_price() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, 10100, 50, 1).unwrap();
+
+        let trades = engine.submit_order(2, Side::Buy, OrderType::Market, 0, 50, 2).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].price, 10100);
+        assert_eq!(engine.book().best_ask(), INVALID_PRICE);
+    }
+
+    #[test]
+    fn test_market_order_cancels_unfilled_remainder() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, 10100, 30, 1).unwrap();
+
+        let trades = engine.submit_order(2, Side::Buy, OrderType::Market, 0, 50, 2).unwrap();
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].quantity, 30);
+        assert_eq!(engine.book().best_bid(), INVALID_PRICE);
+    }
+
+    #[test]
+    fn test_ioc_never_rests() {
+        let mut engine = MatchingEngine::new();
+        let trades =
+            engine.submit_order(1, Side::Buy, OrderType::ImmediateOrCancel, 10000, 100, 1).unwrap();
+
+        assert!(trades.is_empty());
+        assert_eq!(engine.book().best_bid(), INVALID_PRICE);
+    }
+
+    #[test]
+    fn test_fok_rejected_when_not_fully_fillable() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, 10000, 40, 1).unwrap();
+
+        let trades = engine.submit_order(2, Side::Buy, OrderType::FillOrKill, 10000, 100, 2).unwrap();
+
+        assert!(trades.is_empty());
+        assert_eq!(engine.book().ask_quantity_at(10000), 40);
+    }
+
+    #[test]
+    fn test_fok_fills_fully_across_levels() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, 10000, 40, 1).unwrap();
+        engine.submit_order(2, Side::Sell, OrderType::Limit, 10010, 60, 2).unwrap();
+
+        let trades = engine.submit_order(3, Side::Buy, OrderType::FillOrKill, 10010, 100, 3).unwrap();
+
+        assert_eq!(trades.len(), 2);
+        assert_eq!(engine.book().best_ask(), INVALID_PRICE);
+    }
+
+    #[test]
+    fn test_post_only_slides_one_tick_when_crossing() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, 10000, 100, 1).unwrap();
+
+        let trades = engine.submit_order(2, Side::Buy, OrderType::PostOnly, 10000, 100, 2).unwrap();
+
+        assert!(trades.is_empty());
+        assert_eq!(engine.book().bid_quantity_at(10000), 0);
+        assert_eq!(engine.book().bid_quantity_at(9999), 100);
+    }
+
+    #[test]
+    fn test_post_only_rejects_when_crossing_without_slide() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, 0, 100, 1).unwrap();
+
+        let trades = engine.submit_order(2, Side::Buy, OrderType::PostOnly, 0, 100, 2).unwrap();
+
+        assert!(trades.is_empty());
+        assert_eq!(engine.book().best_bid(), INVALID_PRICE);
+        assert_eq!(engine.book().best_ask(), 0);
+    }
+
+    #[test]
+    fn test_post_only_rests_untouched_when_not_crossing() {
+        let mut engine = MatchingEngine::new();
+        engine.submit_order(1, Side::Sell, OrderType::Limit, 10100, 100, 1).unwrap();
+
+        let trades = engine.submit_order(2, Side::Buy, OrderType::PostOnly, 10000, 100, 2).unwrap();
+
+        assert!(trades.is_empty());
+        assert_eq!(engine.book().bid_quantity_at(10000), 100);
+    }
+
+    #[test]
+    fn test_post_only_slides_by_configured_tick_size() {
+        let mut engine = MatchingEngine::with_config(MarketConfig { tick_size: 5, lot_size: 1, min_size: 1 });
+        engine.submit_order(1, Side::Sell, OrderType::Limit, 10000, 100, 1).unwrap();
+
+        let trades = engine.submit_order(2, Side::Buy, OrderType::PostOnly, 10000, 100, 2).unwrap();
+
+        assert!(trades.is_empty());
+        assert_eq!(engine.book().bid_quantity_at(10000), 0);
+        assert_eq!(engine.book().bid_quantity_at(9995), 100);
+    }
+
+    #[test]
+    fn test_submit_order_rejects_quantity_below_market_config_minimum() {
+        let mut engine =
+            MatchingEngine::with_config(MarketConfig { tick_size: 1, lot_size: 1, min_size: 50 });
+
+        let result = engine.submit_order(1, Side::Buy, OrderType::Limit, 10000, 10, 1);
+
+        assert_eq!(result, Err(OrderBookError::BelowMinimumSize));
+        assert_eq!(engine.book().best_bid(), INVALID_PRICE);
+    }
+}
@@ -1,46 +1,202 @@
+use crate::events::{Event, EventSink};
 use crate::types::*;
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
 
-/// Price level containing all orders at a specific price
+/// Price level containing all orders at a specific price. The price itself
+/// is carried by the map key in `bid_levels`/`ask_levels`, not stored here.
 #[derive(Debug, Default)]
 struct PriceLevel {
-    price: Price,
     total_quantity: Quantity,
     orders: Vec<OrderId>,  // Order IDs at this level (FIFO)
 }
 
+/// An aggregated price level as seen by market data consumers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelView {
+    pub price: Price,
+    pub quantity: Quantity,
+    pub order_count: u32,
+}
+
+impl LevelView {
+    fn from_level(price: Price, level: &PriceLevel) -> Self {
+        Self { price, quantity: level.total_quantity, order_count: level.orders.len() as u32 }
+    }
+}
+
+/// Top-of-book ladder returned by [`OrderBook::depth`]
+#[derive(Debug, Clone, Default)]
+pub struct DepthSnapshot {
+    /// Best-to-worst (highest price first)
+    pub bids: Vec<LevelView>,
+    /// Best-to-worst (lowest price first)
+    pub asks: Vec<LevelView>,
+}
+
+/// Per-market granularity constraints an order must satisfy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketConfig {
+    /// Price must be a multiple of this
+    pub tick_size: Price,
+    /// Quantity must be a multiple of this
+    pub lot_size: Quantity,
+    /// Quantity must be at least this
+    pub min_size: Quantity,
+}
+
+impl Default for MarketConfig {
+    /// No constraints: every tick and every lot is valid
+    fn default() -> Self {
+        Self { tick_size: 1, lot_size: 1, min_size: 1 }
+    }
+}
+
+/// Why an order was rejected by [`OrderBook::add_order`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderBookError {
+    /// Price is not a multiple of `MarketConfig::tick_size`
+    InvalidTickSize,
+    /// Quantity is not a multiple of `MarketConfig::lot_size`
+    InvalidLotSize,
+    /// Quantity is below `MarketConfig::min_size`
+    BelowMinimumSize,
+}
+
+/// A resting order whose limit price tracks a reference/oracle price plus
+/// a fixed signed offset, rather than an absolute price
+#[derive(Debug, Clone, Copy)]
+struct PeggedOrder {
+    side: Side,
+    offset: i64,
+    quantity: Quantity,
+    /// Buys never peg above this price, sells never peg below it
+    bound: Option<Price>,
+    /// Price last computed by `reprice_pegged`; meaningful only if `is_valid`
+    effective_price: Price,
+    /// `false` while the order would cross the book at the current
+    /// reference price; such orders are not resting and are skipped by
+    /// best-price computation and matching
+    is_valid: bool,
+}
+
 /// HFT-grade Order Book
 /// - Pre-allocated storage with HashMap for O(1) lookup
-/// - Sorted price levels using BTreeMap-like approach
+/// - Levels kept in a `HashMap<Price, PriceLevel>` for O(1) mutation, with a
+///   `BTreeSet` of occupied prices tracking sort order so lookup/insert of a
+///   level is O(log n) in the number of distinct price levels rather than
+///   O(n) as a flat `Vec` scan would be
 /// - O(1) best bid/ask via cached values
 pub struct OrderBook {
     /// All orders indexed by ID
     orders: HashMap<OrderId, Order>,
 
-    /// Bid levels sorted by price (descending)
-    bid_levels: Vec<PriceLevel>,
+    /// Bid levels indexed by price
+    bid_levels: HashMap<Price, PriceLevel>,
+
+    /// Ask levels indexed by price
+    ask_levels: HashMap<Price, PriceLevel>,
 
-    /// Ask levels sorted by price (ascending)
-    ask_levels: Vec<PriceLevel>,
+    /// Occupied bid prices, highest first
+    bid_prices: BTreeSet<Reverse<Price>>,
+
+    /// Occupied ask prices, lowest first
+    ask_prices: BTreeSet<Price>,
 
     /// Cached best prices for O(1) access
     best_bid: Option<Price>,
     best_ask: Option<Price>,
+
+    /// Granularity constraints incoming orders are validated against
+    config: MarketConfig,
+
+    /// Oracle-pegged orders, keyed by order ID. A valid pegged order also
+    /// has a resting representation in `orders`/`bid_levels`/`ask_levels`
+    /// at its current `effective_price`, kept in sync by `reprice_pegged`
+    /// and by `execute_order` (a fully filled pegged order is dropped).
+    pegged_orders: HashMap<OrderId, PeggedOrder>,
+
+    /// Current reference/oracle price, if one has been set
+    reference_price: Option<Price>,
+
+    /// Optional recorder of state transitions, for replay
+    event_sink: Option<Box<dyn EventSink>>,
 }
 
 impl OrderBook {
     pub fn new() -> Self {
+        Self::with_config(MarketConfig::default())
+    }
+
+    /// Create a book that validates incoming orders against `config`
+    pub fn with_config(config: MarketConfig) -> Self {
         Self {
             orders: HashMap::with_capacity(1_000_000),
-            bid_levels: Vec::with_capacity(10_000),
-            ask_levels: Vec::with_capacity(10_000),
+            bid_levels: HashMap::with_capacity(10_000),
+            ask_levels: HashMap::with_capacity(10_000),
+            bid_prices: BTreeSet::new(),
+            ask_prices: BTreeSet::new(),
             best_bid: None,
             best_ask: None,
+            config,
+            pegged_orders: HashMap::new(),
+            reference_price: None,
+            event_sink: None,
+        }
+    }
+
+    /// Record every subsequent state transition by pushing it to `sink`
+    pub fn set_event_sink(&mut self, sink: Box<dyn EventSink>) {
+        self.event_sink = Some(sink);
+    }
+
+    /// Stop recording state transitions
+    pub fn clear_event_sink(&mut self) {
+        self.event_sink = None;
+    }
+
+    fn emit(&mut self, event: Event) {
+        if let Some(sink) = self.event_sink.as_mut() {
+            sink.on_event(event);
+        }
+    }
+
+    /// Rebuild a book from scratch by replaying a recorded event log.
+    /// `Delete` events need no action: the `ExecutionVisible` that
+    /// accompanies every full fill already removes the order.
+    pub fn replay(events: &[Event]) -> OrderBook {
+        let mut book = OrderBook::new();
+        for event in events {
+            match *event {
+                Event::Add { order_id, side, price, quantity, timestamp } => {
+                    let _ = book.add_order(order_id, side, price, quantity, timestamp);
+                }
+                Event::Cancel { order_id, timestamp, .. } => {
+                    book.cancel_order(order_id, timestamp);
+                }
+                Event::ExecutionVisible { order_id, quantity, timestamp, .. } => {
+                    book.execute_order(order_id, quantity, timestamp);
+                }
+                Event::Delete { .. } => {}
+            }
         }
+        book
     }
 
     /// Add an order to the book
-    pub fn add_order(&mut self, id: OrderId, side: Side, price: Price, quantity: Quantity) {
+    ///
+    /// Validates `price`/`quantity` against the book's [`MarketConfig`]
+    /// before inserting; rejected orders leave the book unchanged.
+    pub fn add_order(
+        &mut self,
+        id: OrderId,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        timestamp: u64,
+    ) -> Result<(), OrderBookError> {
+        self.validate(price, quantity)?;
+
         let order = Order::new(id, side, price, quantity);
         self.orders.insert(id, order);
 
@@ -48,15 +204,169 @@ impl OrderBook {
             Side::Buy => self.add_to_bids(id, price, quantity),
             Side::Sell => self.add_to_asks(id, price, quantity),
         }
+
+        self.emit(Event::Add { timestamp, order_id: id, side, price, quantity });
+        Ok(())
+    }
+
+    /// Check `price`/`quantity` against this book's [`MarketConfig`] without
+    /// inserting anything. Used by `MatchingEngine` to reject an aggressive
+    /// order before it ever touches resting liquidity.
+    pub(crate) fn validate_order(&self, price: Price, quantity: Quantity) -> Result<(), OrderBookError> {
+        self.validate(price, quantity)
+    }
+
+    /// Same as [`OrderBook::validate_order`] but for an order with no price
+    /// of its own (e.g. a `Market` order), so only quantity is checked
+    pub(crate) fn validate_quantity_order(&self, quantity: Quantity) -> Result<(), OrderBookError> {
+        self.validate_quantity(quantity)
+    }
+
+    /// Configured tick size, e.g. for a matching engine to compute a
+    /// tick-aligned slide distance
+    pub(crate) fn tick_size(&self) -> Price {
+        self.config.tick_size.max(1)
+    }
+
+    fn validate(&self, price: Price, quantity: Quantity) -> Result<(), OrderBookError> {
+        // A configured size of 0 would otherwise divide-by-zero below; treat
+        // it the same as 1 (no constraint), matching `round_to_tick`.
+        if !price.is_multiple_of(self.config.tick_size.max(1)) {
+            return Err(OrderBookError::InvalidTickSize);
+        }
+        self.validate_quantity(quantity)
+    }
+
+    fn validate_quantity(&self, quantity: Quantity) -> Result<(), OrderBookError> {
+        if !quantity.is_multiple_of(self.config.lot_size.max(1)) {
+            return Err(OrderBookError::InvalidLotSize);
+        }
+        if quantity < self.config.min_size {
+            return Err(OrderBookError::BelowMinimumSize);
+        }
+        Ok(())
+    }
+
+    /// Add a resting order priced as `reference + offset` rather than an
+    /// absolute price. `bound` caps how far a buy may peg up, or floors how
+    /// far a sell may peg down, regardless of the reference price.
+    ///
+    /// The order rests immediately if a reference price is already set and
+    /// the computed price doesn't cross the book; otherwise it starts
+    /// invalid and rests the first time [`OrderBook::set_reference_price`]
+    /// computes a non-crossing price for it.
+    pub fn add_pegged_order(
+        &mut self,
+        id: OrderId,
+        side: Side,
+        offset: i64,
+        quantity: Quantity,
+        bound: Option<Price>,
+        timestamp: u64,
+    ) -> Result<(), OrderBookError> {
+        self.validate_quantity(quantity)?;
+
+        self.pegged_orders.insert(
+            id,
+            PeggedOrder { side, offset, quantity, bound, effective_price: 0, is_valid: false },
+        );
+
+        if self.reference_price.is_some() {
+            self.reprice_pegged(id, timestamp);
+        }
+
+        Ok(())
+    }
+
+    /// Remove a pegged order, whether or not it's currently resting
+    pub fn remove_pegged_order(&mut self, id: OrderId, timestamp: u64) -> bool {
+        match self.pegged_orders.remove(&id) {
+            Some(peg) => {
+                if peg.is_valid {
+                    self.cancel_order(id, timestamp);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Current effective price of a pegged order, or `None` if it doesn't
+    /// exist or is currently invalid (would cross the book)
+    pub fn pegged_order_price(&self, id: OrderId) -> Option<Price> {
+        self.pegged_orders.get(&id).filter(|p| p.is_valid).map(|p| p.effective_price)
+    }
+
+    /// Update the reference/oracle price and reprice every pegged order
+    /// against it, resting newly-valid orders and pulling newly-crossing
+    /// ones from the book
+    pub fn set_reference_price(&mut self, reference: Price, timestamp: u64) {
+        self.reference_price = Some(reference);
+
+        let ids: Vec<OrderId> = self.pegged_orders.keys().copied().collect();
+        for id in ids {
+            self.reprice_pegged(id, timestamp);
+        }
+    }
+
+    fn reprice_pegged(&mut self, id: OrderId, timestamp: u64) {
+        let Some(reference) = self.reference_price else { return };
+        let Some(peg) = self.pegged_orders.get(&id).copied() else { return };
+
+        let raw = reference as i64 + peg.offset;
+        let bounded = match (peg.side, peg.bound) {
+            (Side::Buy, Some(bound)) => raw.min(bound as i64),
+            (Side::Sell, Some(bound)) => raw.max(bound as i64),
+            _ => raw,
+        };
+        let effective = self.round_to_tick(bounded.clamp(0, Price::MAX as i64) as Price);
+
+        let crosses = match peg.side {
+            Side::Buy => self.side_best(Side::Sell).is_some_and(|best_ask| effective >= best_ask),
+            Side::Sell => self.side_best(Side::Buy).is_some_and(|best_bid| effective <= best_bid),
+        };
+
+        // Nothing actually moved: leave the order resting where it already
+        // is rather than cancel+re-add it to the back of its level's FIFO.
+        if peg.is_valid && !crosses && effective == peg.effective_price {
+            return;
+        }
+
+        if peg.is_valid {
+            self.cancel_order(id, timestamp);
+        }
+
+        let peg_mut = self.pegged_orders.get_mut(&id).expect("just looked up above");
+        peg_mut.effective_price = effective;
+        peg_mut.is_valid = !crosses;
+
+        if !crosses {
+            let _ = self.add_order(id, peg.side, effective, peg.quantity, timestamp);
+        }
+    }
+
+    fn round_to_tick(&self, price: Price) -> Price {
+        let tick = self.config.tick_size.max(1);
+        (price / tick) * tick
     }
 
     /// Cancel an order by ID
-    pub fn cancel_order(&mut self, id: OrderId) -> bool {
+    ///
+    /// For a pegged order, prefer [`OrderBook::remove_pegged_order`] so it
+    /// also stops repricing on future reference updates.
+    pub fn cancel_order(&mut self, id: OrderId, timestamp: u64) -> bool {
         if let Some(order) = self.orders.remove(&id) {
             match order.side {
                 Side::Buy => self.remove_from_bids(id, order.price, order.quantity),
                 Side::Sell => self.remove_from_asks(id, order.price, order.quantity),
             }
+            self.emit(Event::Cancel {
+                timestamp,
+                order_id: id,
+                side: order.side,
+                price: order.price,
+                quantity: order.quantity,
+            });
             true
         } else {
             false
@@ -64,13 +374,14 @@ impl OrderBook {
     }
 
     /// Execute (partially or fully) an order
-    pub fn execute_order(&mut self, id: OrderId, quantity: Quantity) -> bool {
+    pub fn execute_order(&mut self, id: OrderId, quantity: Quantity, timestamp: u64) -> bool {
         if let Some(order) = self.orders.get_mut(&id) {
             let exec_qty = quantity.min(order.quantity);
             let price = order.price;
             let side = order.side;
+            let fully_filled = exec_qty >= order.quantity;
 
-            if exec_qty >= order.quantity {
+            if fully_filled {
                 // Full execution - remove order
                 self.orders.remove(&id);
                 match side {
@@ -85,6 +396,18 @@ impl OrderBook {
                     Side::Sell => self.reduce_ask_quantity(price, exec_qty),
                 }
             }
+
+            self.emit(Event::ExecutionVisible { timestamp, order_id: id, side, price, quantity: exec_qty });
+            if fully_filled {
+                self.emit(Event::Delete { timestamp, order_id: id, side, price, quantity: exec_qty });
+            }
+
+            // Keep a pegged order's record in step with its resting order
+            if fully_filled {
+                self.pegged_orders.remove(&id);
+            } else if let Some(peg) = self.pegged_orders.get_mut(&id) {
+                peg.quantity -= exec_qty;
+            }
             true
         } else {
             false
@@ -103,104 +426,148 @@ impl OrderBook {
 
     /// Get total quantity at a bid price
     pub fn bid_quantity_at(&self, price: Price) -> Quantity {
-        self.bid_levels
-            .iter()
-            .find(|l| l.price == price)
-            .map(|l| l.total_quantity)
-            .unwrap_or(0)
+        self.bid_levels.get(&price).map(|l| l.total_quantity).unwrap_or(0)
     }
 
     /// Get total quantity at an ask price
     pub fn ask_quantity_at(&self, price: Price) -> Quantity {
-        self.ask_levels
-            .iter()
-            .find(|l| l.price == price)
-            .map(|l| l.total_quantity)
-            .unwrap_or(0)
+        self.ask_levels.get(&price).map(|l| l.total_quantity).unwrap_or(0)
+    }
+
+    /// Top `levels` aggregated price levels on each side, best-to-worst
+    pub fn depth(&self, levels: usize) -> DepthSnapshot {
+        DepthSnapshot {
+            bids: self.iter_side(Side::Buy).take(levels).collect(),
+            asks: self.iter_side(Side::Sell).take(levels).collect(),
+        }
+    }
+
+    /// Iterate aggregated price levels on `side`, best-to-worst (descending
+    /// price for bids, ascending for asks)
+    pub fn iter_side(&self, side: Side) -> impl Iterator<Item = LevelView> + '_ {
+        let levels: Box<dyn Iterator<Item = LevelView> + '_> = match side {
+            Side::Buy => Box::new(self.bid_prices.iter().filter_map(move |Reverse(price)| {
+                self.bid_levels.get(price).map(|l| LevelView::from_level(*price, l))
+            })),
+            Side::Sell => Box::new(self.ask_prices.iter().filter_map(move |price| {
+                self.ask_levels.get(price).map(|l| LevelView::from_level(*price, l))
+            })),
+        };
+        levels
+    }
+
+    // === Crate-internal accessors for the matching engine ===
+
+    /// Best resting price on `side`, or `None` if that side is empty
+    pub(crate) fn side_best(&self, side: Side) -> Option<Price> {
+        match side {
+            Side::Buy => self.best_bid,
+            Side::Sell => self.best_ask,
+        }
+    }
+
+    /// Order ID at the front of the FIFO queue resting on `side` at `price`
+    pub(crate) fn front_order_at(&self, side: Side, price: Price) -> Option<OrderId> {
+        match side {
+            Side::Buy => self.bid_levels.get(&price).and_then(|l| l.orders.first().copied()),
+            Side::Sell => self.ask_levels.get(&price).and_then(|l| l.orders.first().copied()),
+        }
+    }
+
+    /// Remaining quantity of a resting order, if it exists
+    pub(crate) fn order_quantity(&self, id: OrderId) -> Option<Quantity> {
+        self.orders.get(&id).map(|o| o.quantity)
+    }
+
+    /// Total resting quantity on `side` at or better than `limit`
+    /// (every price if `limit` is `None`), used to decide fill-or-kill
+    pub(crate) fn quantity_within(&self, side: Side, limit: Option<Price>) -> u64 {
+        match side {
+            Side::Buy => self
+                .bid_prices
+                .iter()
+                .take_while(|Reverse(p)| limit.is_none_or(|l| *p >= l))
+                .map(|Reverse(p)| self.bid_levels.get(p).map(|lvl| lvl.total_quantity as u64).unwrap_or(0))
+                .sum(),
+            Side::Sell => self
+                .ask_prices
+                .iter()
+                .take_while(|p| limit.is_none_or(|l| **p <= l))
+                .map(|p| self.ask_levels.get(p).map(|lvl| lvl.total_quantity as u64).unwrap_or(0))
+                .sum(),
+        }
     }
 
     // === Private methods ===
 
     fn add_to_bids(&mut self, id: OrderId, price: Price, quantity: Quantity) {
-        // Find or create level
-        if let Some(level) = self.bid_levels.iter_mut().find(|l| l.price == price) {
+        if let Some(level) = self.bid_levels.get_mut(&price) {
             level.orders.push(id);
             level.total_quantity += quantity;
         } else {
-            // Insert new level in sorted order (descending)
-            let level = PriceLevel {
+            self.bid_levels.insert(
                 price,
-                total_quantity: quantity,
-                orders: vec![id],
-            };
-            let pos = self.bid_levels.iter().position(|l| l.price < price).unwrap_or(self.bid_levels.len());
-            self.bid_levels.insert(pos, level);
+                PriceLevel { total_quantity: quantity, orders: vec![id] },
+            );
+            self.bid_prices.insert(Reverse(price));
         }
 
-        // Update best bid
         if self.best_bid.is_none() || price > self.best_bid.unwrap() {
             self.best_bid = Some(price);
         }
     }
 
     fn add_to_asks(&mut self, id: OrderId, price: Price, quantity: Quantity) {
-        // Find or create level
-        if let Some(level) = self.ask_levels.iter_mut().find(|l| l.price == price) {
+        if let Some(level) = self.ask_levels.get_mut(&price) {
             level.orders.push(id);
             level.total_quantity += quantity;
         } else {
-            // Insert new level in sorted order (ascending)
-            let level = PriceLevel {
+            self.ask_levels.insert(
                 price,
-                total_quantity: quantity,
-                orders: vec![id],
-            };
-            let pos = self.ask_levels.iter().position(|l| l.price > price).unwrap_or(self.ask_levels.len());
-            self.ask_levels.insert(pos, level);
+                PriceLevel { total_quantity: quantity, orders: vec![id] },
+            );
+            self.ask_prices.insert(price);
         }
 
-        // Update best ask
         if self.best_ask.is_none() || price < self.best_ask.unwrap() {
             self.best_ask = Some(price);
         }
     }
 
     fn remove_from_bids(&mut self, id: OrderId, price: Price, quantity: Quantity) {
-        if let Some(pos) = self.bid_levels.iter().position(|l| l.price == price) {
-            let level = &mut self.bid_levels[pos];
+        if let Some(level) = self.bid_levels.get_mut(&price) {
             level.orders.retain(|&oid| oid != id);
             level.total_quantity = level.total_quantity.saturating_sub(quantity);
 
             if level.total_quantity == 0 {
-                self.bid_levels.remove(pos);
-                // Update best bid
-                self.best_bid = self.bid_levels.first().map(|l| l.price);
+                self.bid_levels.remove(&price);
+                self.bid_prices.remove(&Reverse(price));
+                self.best_bid = self.bid_prices.iter().next().map(|Reverse(p)| *p);
             }
         }
     }
 
     fn remove_from_asks(&mut self, id: OrderId, price: Price, quantity: Quantity) {
-        if let Some(pos) = self.ask_levels.iter().position(|l| l.price == price) {
-            let level = &mut self.ask_levels[pos];
+        if let Some(level) = self.ask_levels.get_mut(&price) {
             level.orders.retain(|&oid| oid != id);
             level.total_quantity = level.total_quantity.saturating_sub(quantity);
 
             if level.total_quantity == 0 {
-                self.ask_levels.remove(pos);
-                // Update best ask
-                self.best_ask = self.ask_levels.first().map(|l| l.price);
+                self.ask_levels.remove(&price);
+                self.ask_prices.remove(&price);
+                self.best_ask = self.ask_prices.iter().next().copied();
             }
         }
     }
 
     fn reduce_bid_quantity(&mut self, price: Price, quantity: Quantity) {
-        if let Some(level) = self.bid_levels.iter_mut().find(|l| l.price == price) {
+        if let Some(level) = self.bid_levels.get_mut(&price) {
             level.total_quantity = level.total_quantity.saturating_sub(quantity);
         }
     }
 
     fn reduce_ask_quantity(&mut self, price: Price, quantity: Quantity) {
-        if let Some(level) = self.ask_levels.iter_mut().find(|l| l.price == price) {
+        if let Some(level) = self.ask_levels.get_mut(&price) {
             level.total_quantity = level.total_quantity.saturating_sub(quantity);
         }
     }
@@ -228,7 +595,7 @@ mod tests {
     #[test]
     fn test_add_buy_order() {
         let mut book = OrderBook::new();
-        book.add_order(1, Side::Buy, 10000, 100);
+        book.add_order(1, Side::Buy, 10000, 100, 1).unwrap();
 
         assert_eq!(book.best_bid(), 10000);
         assert_eq!(book.best_ask(), INVALID_PRICE);
@@ -238,7 +605,7 @@ mod tests {
     #[test]
     fn test_add_sell_order() {
         let mut book = OrderBook::new();
-        book.add_order(1, Side::Sell, 10100, 50);
+        book.add_order(1, Side::Sell, 10100, 50, 1).unwrap();
 
         assert_eq!(book.best_bid(), INVALID_PRICE);
         assert_eq!(book.best_ask(), 10100);
@@ -248,8 +615,8 @@ mod tests {
     #[test]
     fn test_multiple_orders_same_price() {
         let mut book = OrderBook::new();
-        book.add_order(1, Side::Buy, 10000, 100);
-        book.add_order(2, Side::Buy, 10000, 200);
+        book.add_order(1, Side::Buy, 10000, 100, 1).unwrap();
+        book.add_order(2, Side::Buy, 10000, 200, 2).unwrap();
 
         assert_eq!(book.best_bid(), 10000);
         assert_eq!(book.bid_quantity_at(10000), 300);
@@ -258,9 +625,9 @@ mod tests {
     #[test]
     fn test_best_bid_is_highest() {
         let mut book = OrderBook::new();
-        book.add_order(1, Side::Buy, 10000, 100);
-        book.add_order(2, Side::Buy, 10100, 100);
-        book.add_order(3, Side::Buy, 9900, 100);
+        book.add_order(1, Side::Buy, 10000, 100, 1).unwrap();
+        book.add_order(2, Side::Buy, 10100, 100, 2).unwrap();
+        book.add_order(3, Side::Buy, 9900, 100, 3).unwrap();
 
         assert_eq!(book.best_bid(), 10100);
     }
@@ -268,9 +635,9 @@ mod tests {
     #[test]
     fn test_best_ask_is_lowest() {
         let mut book = OrderBook::new();
-        book.add_order(1, Side::Sell, 10200, 100);
-        book.add_order(2, Side::Sell, 10100, 100);
-        book.add_order(3, Side::Sell, 10300, 100);
+        book.add_order(1, Side::Sell, 10200, 100, 1).unwrap();
+        book.add_order(2, Side::Sell, 10100, 100, 2).unwrap();
+        book.add_order(3, Side::Sell, 10300, 100, 3).unwrap();
 
         assert_eq!(book.best_ask(), 10100);
     }
@@ -278,18 +645,18 @@ mod tests {
     #[test]
     fn test_cancel_order() {
         let mut book = OrderBook::new();
-        book.add_order(1, Side::Buy, 10000, 100);
-        book.add_order(2, Side::Buy, 10000, 200);
+        book.add_order(1, Side::Buy, 10000, 100, 1).unwrap();
+        book.add_order(2, Side::Buy, 10000, 200, 2).unwrap();
 
-        assert!(book.cancel_order(1));
+        assert!(book.cancel_order(1, 3));
         assert_eq!(book.bid_quantity_at(10000), 200);
     }
 
     #[test]
     fn test_cancel_removes_price_level() {
         let mut book = OrderBook::new();
-        book.add_order(1, Side::Buy, 10000, 100);
-        book.cancel_order(1);
+        book.add_order(1, Side::Buy, 10000, 100, 1).unwrap();
+        book.cancel_order(1, 2);
 
         assert_eq!(book.best_bid(), INVALID_PRICE);
         assert_eq!(book.bid_quantity_at(10000), 0);
@@ -298,8 +665,8 @@ mod tests {
     #[test]
     fn test_partial_execution() {
         let mut book = OrderBook::new();
-        book.add_order(1, Side::Buy, 10000, 100);
-        book.execute_order(1, 30);
+        book.add_order(1, Side::Buy, 10000, 100, 1).unwrap();
+        book.execute_order(1, 30, 2);
 
         assert_eq!(book.bid_quantity_at(10000), 70);
     }
@@ -307,8 +674,8 @@ mod tests {
     #[test]
     fn test_full_execution() {
         let mut book = OrderBook::new();
-        book.add_order(1, Side::Buy, 10000, 100);
-        book.execute_order(1, 100);
+        book.add_order(1, Side::Buy, 10000, 100, 1).unwrap();
+        book.execute_order(1, 100, 2);
 
         assert_eq!(book.best_bid(), INVALID_PRICE);
         assert_eq!(book.bid_quantity_at(10000), 0);
@@ -317,6 +684,270 @@ mod tests {
     #[test]
     fn test_cancel_nonexistent() {
         let mut book = OrderBook::new();
-        assert!(!book.cancel_order(999));
+        assert!(!book.cancel_order(999, 1));
+    }
+
+    #[test]
+    fn test_best_bid_updates_after_removing_top_of_many_levels() {
+        let mut book = OrderBook::new();
+        for i in 0..50 {
+            book.add_order(i, Side::Buy, 10000 + i as u32, 10, i).unwrap();
+        }
+        assert_eq!(book.best_bid(), 10049);
+
+        book.cancel_order(49, 50);
+        assert_eq!(book.best_bid(), 10048);
+    }
+
+    #[test]
+    fn test_best_ask_updates_after_removing_top_of_many_levels() {
+        let mut book = OrderBook::new();
+        for i in 0..50 {
+            book.add_order(i, Side::Sell, 10000 + i as u32, 10, i).unwrap();
+        }
+        assert_eq!(book.best_ask(), 10000);
+
+        book.cancel_order(0, 50);
+        assert_eq!(book.best_ask(), 10001);
+    }
+
+    #[test]
+    fn test_rejects_price_not_multiple_of_tick_size() {
+        let mut book = OrderBook::with_config(MarketConfig { tick_size: 5, lot_size: 1, min_size: 1 });
+        assert_eq!(book.add_order(1, Side::Buy, 10002, 100, 1), Err(OrderBookError::InvalidTickSize));
+        assert_eq!(book.best_bid(), INVALID_PRICE);
+    }
+
+    #[test]
+    fn test_rejects_quantity_not_multiple_of_lot_size() {
+        let mut book = OrderBook::with_config(MarketConfig { tick_size: 1, lot_size: 10, min_size: 1 });
+        assert_eq!(book.add_order(1, Side::Buy, 10000, 25, 1), Err(OrderBookError::InvalidLotSize));
+    }
+
+    #[test]
+    fn test_rejects_quantity_below_minimum_size() {
+        let mut book = OrderBook::with_config(MarketConfig { tick_size: 1, lot_size: 1, min_size: 50 });
+        assert_eq!(book.add_order(1, Side::Buy, 10000, 10, 1), Err(OrderBookError::BelowMinimumSize));
+    }
+
+    #[test]
+    fn test_accepts_order_matching_market_config() {
+        let mut book = OrderBook::with_config(MarketConfig { tick_size: 5, lot_size: 10, min_size: 10 });
+        assert_eq!(book.add_order(1, Side::Buy, 10005, 20, 1), Ok(()));
+        assert_eq!(book.bid_quantity_at(10005), 20);
+    }
+
+    #[test]
+    fn test_zero_tick_and_lot_size_treated_as_unconstrained() {
+        let mut book = OrderBook::with_config(MarketConfig { tick_size: 0, lot_size: 0, min_size: 1 });
+        assert_eq!(book.add_order(1, Side::Buy, 10001, 7, 1), Ok(()));
+        assert_eq!(book.bid_quantity_at(10001), 7);
+    }
+
+    #[test]
+    fn test_pegged_order_rests_once_reference_is_set() {
+        let mut book = OrderBook::new();
+        book.add_pegged_order(1, Side::Buy, -100, 50, None, 1).unwrap();
+        assert_eq!(book.pegged_order_price(1), None);
+        assert_eq!(book.best_bid(), INVALID_PRICE);
+
+        book.set_reference_price(10000, 2);
+        assert_eq!(book.pegged_order_price(1), Some(9900));
+        assert_eq!(book.best_bid(), 9900);
+        assert_eq!(book.bid_quantity_at(9900), 50);
+    }
+
+    #[test]
+    fn test_pegged_order_repricing_moves_level() {
+        let mut book = OrderBook::new();
+        book.set_reference_price(10000, 1);
+        book.add_pegged_order(1, Side::Sell, 50, 20, None, 2).unwrap();
+        assert_eq!(book.best_ask(), 10050);
+
+        book.set_reference_price(10100, 3);
+        assert_eq!(book.pegged_order_price(1), Some(10150));
+        assert_eq!(book.best_ask(), 10150);
+        assert_eq!(book.ask_quantity_at(10050), 0);
+    }
+
+    #[test]
+    fn test_reprice_with_unchanged_price_preserves_fifo_order() {
+        let mut book = OrderBook::new();
+        book.set_reference_price(10000, 1);
+        book.add_pegged_order(1, Side::Buy, -100, 50, None, 2).unwrap();
+        assert_eq!(book.pegged_order_price(1), Some(9900));
+
+        // A plain order joins the same level behind the pegged order.
+        book.add_order(2, Side::Buy, 9900, 30, 3).unwrap();
+
+        // Re-asserting the same reference price must not requeue order 1
+        // behind order 2, since its effective price didn't move.
+        book.set_reference_price(10000, 4);
+        assert_eq!(book.pegged_order_price(1), Some(9900));
+        assert_eq!(book.bid_quantity_at(9900), 80);
+        assert_eq!(book.front_order_at(Side::Buy, 9900), Some(1));
+    }
+
+    #[test]
+    fn test_pegged_order_invalidated_when_reference_update_would_cross() {
+        let mut book = OrderBook::new();
+        book.add_order(1, Side::Sell, 10000, 100, 1).unwrap();
+        book.set_reference_price(9000, 2);
+        book.add_pegged_order(2, Side::Buy, 500, 10, None, 3).unwrap();
+        assert_eq!(book.pegged_order_price(2), Some(9500));
+
+        // Reference jumps so the pegged buy would cross the resting ask
+        book.set_reference_price(9900, 4);
+        assert_eq!(book.pegged_order_price(2), None);
+        assert_eq!(book.bid_quantity_at(10400), 0);
+    }
+
+    #[test]
+    fn test_pegged_order_clamped_to_bound() {
+        let mut book = OrderBook::new();
+        book.set_reference_price(10000, 1);
+        book.add_pegged_order(1, Side::Buy, 500, 30, Some(10200), 2).unwrap();
+
+        assert_eq!(book.pegged_order_price(1), Some(10200));
+
+        book.set_reference_price(10500, 3);
+        assert_eq!(book.pegged_order_price(1), Some(10200));
+    }
+
+    #[test]
+    fn test_remove_pegged_order() {
+        let mut book = OrderBook::new();
+        book.set_reference_price(10000, 1);
+        book.add_pegged_order(1, Side::Buy, 0, 10, None, 2).unwrap();
+        assert_eq!(book.best_bid(), 10000);
+
+        assert!(book.remove_pegged_order(1, 3));
+        assert_eq!(book.best_bid(), INVALID_PRICE);
+        assert_eq!(book.pegged_order_price(1), None);
+    }
+
+    #[test]
+    fn test_iter_side_best_to_worst() {
+        let mut book = OrderBook::new();
+        book.add_order(1, Side::Buy, 9900, 10, 1).unwrap();
+        book.add_order(2, Side::Buy, 10000, 20, 2).unwrap();
+        book.add_order(3, Side::Buy, 9950, 5, 3).unwrap();
+
+        let bids: Vec<LevelView> = book.iter_side(Side::Buy).collect();
+        assert_eq!(
+            bids,
+            vec![
+                LevelView { price: 10000, quantity: 20, order_count: 1 },
+                LevelView { price: 9950, quantity: 5, order_count: 1 },
+                LevelView { price: 9900, quantity: 10, order_count: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_depth_aggregates_per_level_and_respects_limit() {
+        let mut book = OrderBook::new();
+        book.add_order(1, Side::Buy, 10000, 10, 1).unwrap();
+        book.add_order(2, Side::Buy, 10000, 15, 2).unwrap();
+        book.add_order(3, Side::Buy, 9900, 5, 3).unwrap();
+        book.add_order(4, Side::Sell, 10100, 8, 4).unwrap();
+        book.add_order(5, Side::Sell, 10200, 12, 5).unwrap();
+
+        let depth = book.depth(1);
+        assert_eq!(depth.bids, vec![LevelView { price: 10000, quantity: 25, order_count: 2 }]);
+        assert_eq!(depth.asks, vec![LevelView { price: 10100, quantity: 8, order_count: 1 }]);
+    }
+
+    #[test]
+    fn test_event_sink_records_add_cancel_and_execution() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        struct Shared(Rc<RefCell<Vec<Event>>>);
+        impl EventSink for Shared {
+            fn on_event(&mut self, event: Event) {
+                self.0.borrow_mut().push(event);
+            }
+        }
+
+        let recorded = Rc::new(RefCell::new(Vec::new()));
+        let mut book = OrderBook::new();
+        book.set_event_sink(Box::new(Shared(Rc::clone(&recorded))));
+
+        book.add_order(1, Side::Buy, 10000, 100, 1).unwrap();
+        book.add_order(2, Side::Buy, 10000, 50, 2).unwrap();
+        book.execute_order(1, 40, 3);
+        book.cancel_order(2, 4);
+
+        book.clear_event_sink();
+        book.add_order(3, Side::Buy, 10000, 10, 5).unwrap();
+
+        assert_eq!(
+            *recorded.borrow(),
+            vec![
+                Event::Add { timestamp: 1, order_id: 1, side: Side::Buy, price: 10000, quantity: 100 },
+                Event::Add { timestamp: 2, order_id: 2, side: Side::Buy, price: 10000, quantity: 50 },
+                Event::ExecutionVisible { timestamp: 3, order_id: 1, side: Side::Buy, price: 10000, quantity: 40 },
+                Event::Cancel { timestamp: 4, order_id: 2, side: Side::Buy, price: 10000, quantity: 50 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_order_emits_delete_only_on_full_fill() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        #[derive(Default)]
+        struct Counts { adds: u32, executions: u32, deletes: u32 }
+        struct Counting(Rc<RefCell<Counts>>);
+        impl EventSink for Counting {
+            fn on_event(&mut self, event: Event) {
+                let mut counts = self.0.borrow_mut();
+                match event {
+                    Event::Add { .. } => counts.adds += 1,
+                    Event::ExecutionVisible { .. } => counts.executions += 1,
+                    Event::Delete { .. } => counts.deletes += 1,
+                    Event::Cancel { .. } => {}
+                }
+            }
+        }
+
+        let counts = Rc::new(RefCell::new(Counts::default()));
+        let mut book = OrderBook::new();
+        let sink = Box::new(Counting(Rc::clone(&counts)));
+        book.set_event_sink(sink);
+
+        book.add_order(1, Side::Buy, 10000, 100, 1).unwrap();
+        book.execute_order(1, 40, 2); // partial: no delete
+        assert_eq!(counts.borrow().deletes, 0);
+        book.execute_order(1, 60, 3); // full: delete accompanies execution
+
+        let counts = counts.borrow();
+        assert_eq!((counts.adds, counts.executions, counts.deletes), (1, 2, 1));
+    }
+
+    #[test]
+    fn test_replay_reconstructs_equivalent_book() {
+        let mut original = OrderBook::new();
+        original.add_order(1, Side::Buy, 10000, 100, 1).unwrap();
+        original.add_order(2, Side::Buy, 10050, 20, 2).unwrap();
+        original.add_order(3, Side::Sell, 10100, 30, 3).unwrap();
+        original.execute_order(1, 40, 4);
+        original.cancel_order(2, 5);
+
+        let log = vec![
+            Event::Add { timestamp: 1, order_id: 1, side: Side::Buy, price: 10000, quantity: 100 },
+            Event::Add { timestamp: 2, order_id: 2, side: Side::Buy, price: 10050, quantity: 20 },
+            Event::Add { timestamp: 3, order_id: 3, side: Side::Sell, price: 10100, quantity: 30 },
+            Event::ExecutionVisible { timestamp: 4, order_id: 1, side: Side::Buy, price: 10000, quantity: 40 },
+            Event::Cancel { timestamp: 5, order_id: 2, side: Side::Buy, price: 10050, quantity: 20 },
+        ];
+
+        let replayed = OrderBook::replay(&log);
+        assert_eq!(replayed.best_bid(), original.best_bid());
+        assert_eq!(replayed.best_ask(), original.best_ask());
+        assert_eq!(replayed.bid_quantity_at(10000), original.bid_quantity_at(10000));
+        assert_eq!(replayed.ask_quantity_at(10100), original.ask_quantity_at(10100));
     }
 }
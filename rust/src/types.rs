@@ -12,6 +12,16 @@ pub enum Side {
     Sell,
 }
 
+impl Side {
+    /// The side a resting order must be on to cross against this one
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Order {
     pub id: OrderId,